@@ -0,0 +1,112 @@
+//! Spatialize a live microphone input around the listener in real time.
+//!
+//! This mirrors cpal's `feedback` example: an input stream captures mono
+//! frames from the default input device and feeds them into the input half
+//! of an `hrtf_stream`, while an output stream on the default output device
+//! drains the binaural result from its output half. The two halves are
+//! independent, so each callback owns its half outright with no `Mutex`
+//! shared between the two device threads. `LATENCY_MS` controls
+//! how long we wait before starting playback, giving the stream's internal
+//! rings enough headroom to absorb the sync gap between the two devices'
+//! callbacks.
+//!
+//! Point a microphone at yourself and put on headphones to hear your own
+//! voice orbit your head.
+
+extern crate anyhow;
+extern crate cpal;
+
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use hrtf_examples::{hrtf_stream, HrirSphere, Rolloff, Vec3};
+use std::f32::consts::PI;
+use std::fs::File;
+use std::time::Duration;
+
+const LATENCY_MS: f32 = 150.0;
+const BLOCK_LEN: usize = 512;
+const INTERPOLATION_STEPS: usize = 8;
+const ROTATION_HZ: f32 = 0.1;
+const SPEED_OF_SOUND: f32 = 343.0;
+
+fn main() {
+    let host = cpal::default_host();
+
+    let input_device = host
+        .default_input_device()
+        .expect("failed to find a default input device");
+    let output_device = host
+        .default_output_device()
+        .expect("failed to find a default output device");
+
+    let mut input_config: cpal::StreamConfig = input_device
+        .default_input_config()
+        .expect("failed to get default input config")
+        .into();
+    input_config.channels = 1;
+    input_config.sample_rate = cpal::SampleRate(44_100);
+
+    let mut output_config: cpal::StreamConfig = output_device
+        .default_output_config()
+        .expect("failed to get default output config")
+        .into();
+    output_config.channels = 2;
+    output_config.sample_rate = cpal::SampleRate(44_100);
+
+    let sphere = HrirSphere::new(
+        File::open("IRC_1002_C.bin").expect("failed to open HRIR sphere"),
+        output_config.sample_rate.0,
+    )
+    .expect("failed to load HRIR sphere");
+    let (mut hrtf_input, mut hrtf_output) = hrtf_stream(
+        sphere,
+        INTERPOLATION_STEPS,
+        BLOCK_LEN,
+        output_config.channels as usize,
+        output_config.sample_rate.0 as f32,
+        Rolloff::Inverse { reference_distance: 1.0 },
+        SPEED_OF_SOUND,
+    );
+
+    let err_fn = |err| eprintln!("an error occurred on stream: {}", err);
+
+    let mut elapsed_secs = 0.0f32;
+    let input_sample_rate = input_config.sample_rate.0 as f32;
+    let input_stream = input_device
+        .build_input_stream(
+            &input_config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                hrtf_input.push_source(data);
+
+                // Orbit the captured voice around the listener as it plays back.
+                let radians = elapsed_secs * ROTATION_HZ * 2.0 * PI;
+                elapsed_secs += data.len() as f32 / input_sample_rate;
+                hrtf_input.set_position(Vec3::new(radians.cos(), 0.0, radians.sin()));
+            },
+            err_fn,
+            None,
+        )
+        .expect("failed to build input stream");
+
+    let output_stream = output_device
+        .build_output_stream(
+            &output_config,
+            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+                hrtf_output.fill_output(data);
+            },
+            err_fn,
+            None,
+        )
+        .expect("failed to build output stream");
+
+    input_stream.play().expect("failed to play input stream");
+
+    // Give the rings enough headroom to fill before we start draining them,
+    // so the output doesn't immediately underrun.
+    std::thread::sleep(Duration::from_millis(LATENCY_MS as u64));
+
+    output_stream.play().expect("failed to play output stream");
+
+    // Run for a minute; a real host would keep both streams alive for as long
+    // as it needs live spatialization.
+    std::thread::sleep(Duration::from_secs(60));
+}
@@ -4,6 +4,10 @@
 //! position is created at the beginning of each call to the output stream's render function in
 //! order to rotate the sound source around the user's head.
 //!
+//! The HRTF convolution itself runs off of the audio thread: each callback just pushes a fresh
+//! block of noise and the rotated position into a `hrtf_stream`, then drains whatever binaural
+//! output its worker has produced so far.
+//!
 //! The example will fail if the default cpal output device under the default host offers less than
 //! two channels and cannot achieve a sample rate of 44.1 KHz.
 //!
@@ -13,41 +17,75 @@ extern crate anyhow;
 extern crate cpal;
 
 use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
-use rand::{rngs::SmallRng, SeedableRng};
+use hrtf_examples::{hrtf_stream, write_hrtf_frames, HrirSphere, Rolloff, Vec3};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
 use std::f32::consts::PI;
+use std::fs::File;
+
+const BLOCK_LEN: usize = 512;
+const INTERPOLATION_STEPS: usize = 8;
+const SPEED_OF_SOUND: f32 = 343.0;
+
+// The HRTFs are designed for 44.1 KHz.
+const SAMPLE_RATE: cpal::SampleRate = cpal::SampleRate(44_100);
 
-fn main() {
+fn main() -> anyhow::Result<()> {
     let host = cpal::default_host();
     let device = host
         .default_output_device()
         .expect("failed to find a default output device");
-    let mut config = device.default_output_config()?;
 
     // Humans have two ears.
-    config.channels = 2;
-    // The HRTFs are designed for 44.1 KHz
-    config.sample_rate = cpal::SampleRate(44_100);
+    let config = device
+        .supported_output_configs()?
+        .find(|range| {
+            range.channels() == 2
+                && range.min_sample_rate() <= SAMPLE_RATE
+                && range.max_sample_rate() >= SAMPLE_RATE
+        })
+        .expect("default output device doesn't support stereo output at 44.1 KHz")
+        .with_sample_rate(SAMPLE_RATE);
+
+    let sphere = HrirSphere::new(File::open("IRC_1002_C.bin")?, SAMPLE_RATE.0)
+        .map_err(|e| anyhow::anyhow!("failed to load HRIR sphere: {e:?}"))?;
 
     match config.sample_format() {
-        cpal::SampleFormat::F32 => run::<f32>(&device, &config.into()),
-        cpal::SampleFormat::I16 => run::<i16>(&device, &config.into()),
-        cpal::SampleFormat::U16 => run::<u16>(&device, &config.into()),
+        cpal::SampleFormat::F32 => run::<f32>(&device, &config.into(), sphere),
+        cpal::SampleFormat::F64 => run::<f64>(&device, &config.into(), sphere),
+        cpal::SampleFormat::I8 => run::<i8>(&device, &config.into(), sphere),
+        cpal::SampleFormat::I16 => run::<i16>(&device, &config.into(), sphere),
+        cpal::SampleFormat::I32 => run::<i32>(&device, &config.into(), sphere),
+        cpal::SampleFormat::I64 => run::<i64>(&device, &config.into(), sphere),
+        cpal::SampleFormat::U8 => run::<u8>(&device, &config.into(), sphere),
+        cpal::SampleFormat::U16 => run::<u16>(&device, &config.into(), sphere),
+        cpal::SampleFormat::U32 => run::<u32>(&device, &config.into(), sphere),
+        cpal::SampleFormat::U64 => run::<u64>(&device, &config.into(), sphere),
+        other => anyhow::bail!("unsupported sample format: {other}"),
     }
 }
 
 // Run the stream with the specified format.
-fn run<T>(device: &cpal::Device, config: &cpal::StreamConfig)
+fn run<T>(device: &cpal::Device, config: &cpal::StreamConfig, sphere: HrirSphere) -> anyhow::Result<()>
 where
-    T: cpal::Sample,
+    T: cpal::Sample + cpal::FromSample<f32> + cpal::SizedSample,
 {
     let channels = config.channels as usize;
     let sample_rate = config.sample_rate.0 as f32;
-    let volume = 0.25;
     let rotation_hz = 0.5;
     let mut stream_start = None;
 
+    let (mut hrtf_input, mut hrtf_output) = hrtf_stream(
+        sphere,
+        INTERPOLATION_STEPS,
+        BLOCK_LEN,
+        channels,
+        sample_rate,
+        Rolloff::Inverse { reference_distance: 1.0 },
+        SPEED_OF_SOUND,
+    );
+
     // The RNG used to generate the noise.
-    let mut rng = rand::rngs::SmallRng::new();
+    let mut rng = SmallRng::from_entropy();
 
     // Build the output stream.
     let err_fn = |err| eprintln!("an error occurred on stream: {}", err);
@@ -60,34 +98,26 @@ where
             let since_start = now.duration_since(&start).unwrap();
             let secs = since_start.as_secs_f32();
             let radians = secs * rotation_hz * 2.0 * PI;
-            let x = radians.cos();
-            let z = radians.sin();
+            hrtf_input.set_position(Vec3::new(radians.cos(), 0.0, radians.sin()));
 
-            // Create a monophonic buffer of noise. Normally we shouldn't dynamically allocate on
-            // the audio thread like this, but it's just a quick demo.
+            // Push a fresh block of noise into the stream. Normally we shouldn't dynamically
+            // allocate on the audio thread like this, but it's just a quick demo.
             let frame_count = data.len() / channels;
-            let noise: Vec<_> = (..frame_count).map(|_| rng.gen::<f32>()).collect();
+            let noise: Vec<f32> = (0..frame_count).map(|_| rng.gen::<f32>() * 2.0 - 1.0).collect();
+            hrtf_input.push_source(&noise);
 
-            write_data(data, channels, &mut next_value)
+            let mut rendered = vec![0.0; frame_count * 2];
+            hrtf_output.fill_output(&mut rendered);
+            let stereo: Vec<[f32; 2]> = rendered.chunks(2).map(|pair| [pair[0], pair[1]]).collect();
+            write_hrtf_frames(data, channels, &stereo);
         },
         err_fn,
-    ).expect("failed to build output stream");
-    stream.play().expect("failed to play stream");
+        None,
+    )?;
+    stream.play()?;
 
     // Stop after 10 seconds.
     std::thread::sleep(std::time::Duration::from_secs(10));
 
     Ok(())
 }
-
-fn write_data<T>(output: &mut [T], channels: usize, rng: &mut dyn FnMut() -> f32)
-where
-    T: cpal::Sample,
-{
-    for frame in output.chunks_mut(channels) {
-        let value: T = cpal::Sample::from::<f32>(&next_sample());
-        for sample in frame.iter_mut() {
-            *sample = value;
-        }
-    }
-}
@@ -0,0 +1,61 @@
+//! Render a source orbiting the listener to a binaural WAV file, offline.
+//!
+//! Unlike the `cpal-rotating-noise` example, this doesn't touch an audio
+//! device at all: it generates a monophonic buffer of noise, steps it through
+//! [`render_to_wav`] along a circular trajectory, and writes the result to
+//! `rotating-noise.wav` in the current directory. Useful for producing
+//! deterministic test vectors without any hardware in the loop.
+
+extern crate hound;
+extern crate rand;
+
+use hrtf_examples::{render_to_wav, HrirSphere, Vec3};
+use rand::{rngs::SmallRng, Rng, SeedableRng};
+use std::f32::consts::PI;
+use std::fs::File;
+
+const SAMPLE_RATE: u32 = 44_100;
+const DURATION_SECS: u32 = 10;
+const ROTATION_HZ: f32 = 0.5;
+const BLOCK_LEN: usize = 512;
+const INTERPOLATION_STEPS: usize = 8;
+
+fn main() {
+    let sphere = HrirSphere::new(
+        File::open("IRC_1002_C.bin").expect("failed to open HRIR sphere"),
+        SAMPLE_RATE,
+    )
+    .expect("failed to load HRIR sphere");
+
+    let mut rng = SmallRng::from_entropy();
+    let frame_count = (SAMPLE_RATE * DURATION_SECS) as usize;
+    let source: Vec<f32> = (0..frame_count).map(|_| rng.gen::<f32>() * 2.0 - 1.0).collect();
+
+    // A keyframe once per block is plenty to describe a smooth circular orbit.
+    let trajectory = (0..frame_count).step_by(BLOCK_LEN).map(|sample_index| {
+        let secs = sample_index as f32 / SAMPLE_RATE as f32;
+        let radians = secs * ROTATION_HZ * 2.0 * PI;
+        (sample_index, Vec3::new(radians.cos(), 0.0, radians.sin()))
+    });
+
+    let spec = hound::WavSpec {
+        channels: 2,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create("rotating-noise.wav", spec)
+        .expect("failed to create rotating-noise.wav");
+
+    render_to_wav(
+        sphere,
+        INTERPOLATION_STEPS,
+        BLOCK_LEN,
+        &source,
+        trajectory,
+        &mut writer,
+    )
+    .expect("failed to render to wav");
+
+    writer.finalize().expect("failed to finalize rotating-noise.wav");
+}
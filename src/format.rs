@@ -0,0 +1,49 @@
+/// Interleave HRTF stereo output into any `cpal` output buffer format.
+///
+/// `stereo` holds one `[left, right]` pair per frame. Channels beyond the
+/// first two are zero-filled, and `out` may hold fewer frames than `stereo`
+/// (the remainder is simply left untouched) to tolerate a caller draining a
+/// ring buffer in irregularly sized chunks.
+///
+/// This lets a host run HRTF straight on a device's native sample format
+/// instead of forcing the stream to be rebuilt as `f32`.
+pub fn write_hrtf_frames<T>(out: &mut [T], channels: usize, stereo: &[[f32; 2]])
+where
+    T: cpal::Sample + cpal::FromSample<f32>,
+{
+    for (frame, pair) in out.chunks_mut(channels).zip(stereo) {
+        for (channel, sample) in frame.iter_mut().enumerate() {
+            let value = pair.get(channel).copied().unwrap_or(0.0);
+            *sample = T::from_sample(value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interleaves_stereo_into_stereo_output() {
+        let stereo = [[0.5, -0.5], [1.0, -1.0]];
+        let mut out = [0.0f32; 4];
+        write_hrtf_frames(&mut out, 2, &stereo);
+        assert_eq!(out, [0.5, -0.5, 1.0, -1.0]);
+    }
+
+    #[test]
+    fn zero_fills_channels_beyond_stereo() {
+        let stereo = [[0.5, -0.5]];
+        let mut out = [1.0f32; 4];
+        write_hrtf_frames(&mut out, 4, &stereo);
+        assert_eq!(out, [0.5, -0.5, 0.0, 0.0]);
+    }
+
+    #[test]
+    fn leaves_trailing_frames_untouched_on_underrun() {
+        let stereo = [[0.5, -0.5]];
+        let mut out = [9.0f32; 4];
+        write_hrtf_frames(&mut out, 2, &stereo);
+        assert_eq!(out, [0.5, -0.5, 9.0, 9.0]);
+    }
+}
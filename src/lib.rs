@@ -0,0 +1,22 @@
+//! Real-time and offline helpers built around the [`hrtf`] crate's
+//! `HrtfProcessor`.
+//!
+//! The examples in this crate show the processor wired up to `cpal`, but the
+//! convolution it performs (FFT overlap-add per block) is too expensive to run
+//! reliably on the audio callback thread itself. The types here move that work
+//! off of the callback thread and provide the plumbing needed to drive it from
+//! a real device, a live microphone, or an offline render.
+
+pub use hrtf::{HrirSphere, HrtfContext, HrtfProcessor, Vec3};
+
+mod format;
+mod motion;
+mod offline;
+mod stream;
+#[cfg(test)]
+mod test_fixtures;
+
+pub use format::write_hrtf_frames;
+pub use motion::{Rolloff, SourceMotion};
+pub use offline::render_to_wav;
+pub use stream::{hrtf_stream, HrtfStreamInput, HrtfStreamOutput};
@@ -0,0 +1,187 @@
+use crate::{HrirSphere, HrtfContext, HrtfProcessor, Vec3};
+use hound::WavWriter;
+use std::io::{Seek, Write};
+
+/// Render a mono `source` signal along a `trajectory` of listener-relative
+/// positions to a binaural stereo WAV file.
+///
+/// `trajectory` yields `(sample_index, position)` keyframes in ascending
+/// order of `sample_index`. The position for each chunk is linearly
+/// interpolated from the surrounding keyframes; positions before the first
+/// keyframe or after the last hold at that keyframe's value.
+///
+/// Unlike [`hrtf_stream`](crate::hrtf_stream), this runs entirely on the
+/// calling thread: there's no audio callback to avoid blocking, so it's
+/// useful for generating deterministic test vectors or baking spatialized
+/// assets ahead of time.
+pub fn render_to_wav<W: Write + Seek>(
+    sphere: HrirSphere,
+    interpolation_steps: usize,
+    block_len: usize,
+    source: &[f32],
+    trajectory: impl IntoIterator<Item = (usize, Vec3)>,
+    out: &mut WavWriter<W>,
+) -> Result<(), hound::Error> {
+    let chunk_len = interpolation_steps * block_len;
+    let keyframes: Vec<(usize, Vec3)> = trajectory.into_iter().collect();
+    let mut processor = HrtfProcessor::new(sphere, interpolation_steps, block_len);
+
+    let mut output_chunk = vec![(0.0, 0.0); chunk_len];
+    let mut prev_left_samples = Vec::new();
+    let mut prev_right_samples = Vec::new();
+    let mut prev_sample_vector = position_at(&keyframes, 0);
+    let mut prev_distance_gain = 1.0;
+
+    for chunk_start in (0..source.len()).step_by(chunk_len) {
+        let chunk_end = (chunk_start + chunk_len).min(source.len());
+        let mut source_chunk = vec![0.0; chunk_len];
+        source_chunk[..chunk_end - chunk_start].copy_from_slice(&source[chunk_start..chunk_end]);
+
+        let new_sample_vector = position_at(&keyframes, chunk_start);
+
+        // `process_samples` mixes into `output` rather than overwriting it,
+        // so it has to start from silence each chunk.
+        output_chunk.iter_mut().for_each(|s| *s = (0.0, 0.0));
+
+        let context = HrtfContext {
+            source: &source_chunk,
+            output: &mut output_chunk,
+            new_sample_vector,
+            prev_sample_vector,
+            prev_left_samples: &mut prev_left_samples,
+            prev_right_samples: &mut prev_right_samples,
+            prev_distance_gain,
+            new_distance_gain: 1.0,
+        };
+        processor.process_samples(context);
+
+        prev_sample_vector = new_sample_vector;
+        prev_distance_gain = 1.0;
+
+        for &(left, right) in &output_chunk[..chunk_end - chunk_start] {
+            out.write_sample(left)?;
+            out.write_sample(right)?;
+        }
+    }
+
+    Ok(())
+}
+
+// Linearly interpolate the position at `sample_index` from the surrounding
+// keyframes, holding at the nearest keyframe outside the trajectory's range.
+fn position_at(keyframes: &[(usize, Vec3)], sample_index: usize) -> Vec3 {
+    match keyframes.iter().position(|&(i, _)| i > sample_index) {
+        None => keyframes
+            .last()
+            .map(|&(_, p)| p)
+            .unwrap_or_else(|| Vec3::new(0.0, 0.0, 1.0)),
+        Some(0) => keyframes[0].1,
+        Some(next) => {
+            let (i0, p0) = keyframes[next - 1];
+            let (i1, p1) = keyframes[next];
+            let t = (sample_index - i0) as f32 / (i1 - i0) as f32;
+            Vec3::new(
+                p0.x + (p1.x - p0.x) * t,
+                p0.y + (p1.y - p0.y) * t,
+                p0.z + (p1.z - p0.z) * t,
+            )
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_vec3_eq(a: Vec3, b: Vec3) {
+        assert_eq!((a.x, a.y, a.z), (b.x, b.y, b.z));
+    }
+
+    #[test]
+    fn position_at_holds_before_first_keyframe() {
+        let keyframes = [(100, Vec3::new(1.0, 0.0, 0.0)), (200, Vec3::new(0.0, 0.0, 1.0))];
+        assert_vec3_eq(position_at(&keyframes, 0), Vec3::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn position_at_holds_after_last_keyframe() {
+        let keyframes = [(100, Vec3::new(1.0, 0.0, 0.0)), (200, Vec3::new(0.0, 0.0, 1.0))];
+        assert_vec3_eq(position_at(&keyframes, 500), Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn position_at_holds_with_no_keyframes() {
+        assert_vec3_eq(position_at(&[], 42), Vec3::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn position_at_interpolates_between_keyframes() {
+        let keyframes = [(0, Vec3::new(0.0, 0.0, 0.0)), (100, Vec3::new(10.0, 0.0, 0.0))];
+        assert_vec3_eq(position_at(&keyframes, 50), Vec3::new(5.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn position_at_on_exact_keyframe_returns_it_unchanged() {
+        let keyframes = [(0, Vec3::new(0.0, 0.0, 0.0)), (100, Vec3::new(10.0, 0.0, 0.0))];
+        assert_vec3_eq(position_at(&keyframes, 100), Vec3::new(10.0, 0.0, 0.0));
+    }
+
+    // Regression test for an output-accumulation bug: `process_samples` mixes
+    // into `output` rather than overwriting it, so reusing `output_chunk`
+    // across iterations without clearing it first summed every prior chunk's
+    // result on top of the current one, making the rendered amplitude grow
+    // without bound over a long source. A constant source at a fixed position
+    // should render identical-magnitude chunks throughout.
+    #[test]
+    fn render_to_wav_does_not_accumulate_across_chunks() {
+        let interpolation_steps = 2;
+        let block_len = 4;
+        let chunk_len = interpolation_steps * block_len;
+        let sample_rate = 44_100;
+        let source = vec![1.0f32; chunk_len * 3];
+        let trajectory = [(0, Vec3::new(0.0, 0.0, 1.0))];
+
+        let spec = hound::WavSpec {
+            channels: 2,
+            sample_rate,
+            bits_per_sample: 32,
+            sample_format: hound::SampleFormat::Float,
+        };
+        let mut cursor = std::io::Cursor::new(Vec::new());
+        {
+            let mut writer = hound::WavWriter::new(&mut cursor, spec).unwrap();
+            render_to_wav(
+                crate::test_fixtures::synthetic_hrir_sphere(sample_rate),
+                interpolation_steps,
+                block_len,
+                &source,
+                trajectory,
+                &mut writer,
+            )
+            .unwrap();
+            writer.finalize().unwrap();
+        }
+
+        cursor.set_position(0);
+        let mut reader = hound::WavReader::new(cursor).unwrap();
+        let samples: Vec<f32> = reader.samples::<f32>().map(Result::unwrap).collect();
+
+        // One (left, right) pair per source sample.
+        let chunk_magnitudes: Vec<f32> = samples
+            .chunks(chunk_len * 2)
+            .map(|chunk| chunk.iter().fold(0.0f32, |max, &s| max.max(s.abs())))
+            .collect();
+        assert_eq!(chunk_magnitudes.len(), 3);
+        let first = chunk_magnitudes[0];
+        assert!(first > 0.0, "expected non-silent output, got {:?}", chunk_magnitudes);
+        for (i, &magnitude) in chunk_magnitudes.iter().enumerate() {
+            assert!(
+                (magnitude - first).abs() < first * 0.01,
+                "chunk {} magnitude {} diverged from chunk 0's {} — output is accumulating across chunks",
+                i,
+                magnitude,
+                first
+            );
+        }
+    }
+}
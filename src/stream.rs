@@ -0,0 +1,265 @@
+use crate::{HrirSphere, HrtfContext, HrtfProcessor, Rolloff, SourceMotion, Vec3};
+use ringbuf::{Consumer, HeapRb, Producer};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+// How long the worker sleeps between polls when it doesn't yet have enough
+// source samples to fill a block. Short enough to keep latency low, long
+// enough not to spin the core.
+const WORKER_PARK: Duration = Duration::from_millis(1);
+
+/// Spawn a worker thread that decouples HRTF convolution from playback, and
+/// split the audio-thread-facing ends of it into two independent handles.
+/// Source samples and rendered frames travel through lock-free rings; the
+/// target position is shared with the worker through a `Mutex`, so
+/// [`HrtfStreamInput::set_position`] is not lock-free.
+///
+/// [`HrtfStreamInput`] and [`HrtfStreamOutput`] touch disjoint state, so a
+/// capture callback and a playback callback running on two different device
+/// threads can each own one directly with no `Mutex` between them. The worker
+/// pulls mono source samples and the latest target position from ring
+/// buffers, runs `process_samples` in `interpolation_steps * block_len`-sized
+/// chunks with overlap-add carry-over between blocks, and pushes the
+/// resulting interleaved stereo frames into an output ring that
+/// `HrtfStreamOutput` drains.
+///
+/// `block_len` and `interpolation_steps` are forwarded to
+/// [`HrtfProcessor::new`], which requires each chunk passed to
+/// `process_samples` to hold exactly `interpolation_steps * block_len`
+/// samples. `channels` is the number of interleaved output channels; only the
+/// first two (left and right) are written, any others are zero-filled.
+/// `sample_rate` and `speed_of_sound` (in the same distance unit per second as
+/// source positions) drive the Doppler shift derived from the source's motion
+/// between chunks; `rolloff` drives its distance attenuation. The listener is
+/// fixed at the origin.
+pub fn hrtf_stream(
+    sphere: HrirSphere,
+    interpolation_steps: usize,
+    block_len: usize,
+    channels: usize,
+    sample_rate: f32,
+    rolloff: Rolloff,
+    speed_of_sound: f32,
+) -> (HrtfStreamInput, HrtfStreamOutput) {
+    let chunk_len = interpolation_steps * block_len;
+
+    // A couple of chunks of headroom on each ring absorbs scheduling jitter
+    // between the audio thread(s) and the worker thread.
+    let source_ring = HeapRb::<f32>::new(chunk_len * 4);
+    let (source_producer, mut source_consumer) = source_ring.split();
+
+    let output_ring = HeapRb::<f32>::new(chunk_len * channels * 2 + channels);
+    let (mut output_producer, output_consumer) = output_ring.split();
+
+    let position = Arc::new(Mutex::new(Vec3::new(0.0, 0.0, 1.0)));
+    let worker_position = Arc::clone(&position);
+
+    let running = Arc::new(AtomicBool::new(true));
+    let worker_running = Arc::clone(&running);
+
+    let listener = Vec3::new(0.0, 0.0, 0.0);
+    let chunk_duration = chunk_len as f32 / sample_rate;
+
+    let worker = thread::spawn(move || {
+        let mut processor = HrtfProcessor::new(sphere, interpolation_steps, block_len);
+        let initial_position = *worker_position.lock().unwrap();
+        let mut motion = SourceMotion::new(rolloff, speed_of_sound, initial_position);
+        let mut raw_chunk = vec![0.0; chunk_len];
+        let mut source_chunk = vec![0.0; chunk_len];
+        let mut output_chunk = vec![(0.0, 0.0); chunk_len];
+        let mut output_frame = vec![0.0; channels];
+        let mut prev_left_samples = Vec::new();
+        let mut prev_right_samples = Vec::new();
+        let mut prev_sample_vector = initial_position;
+        let mut prev_distance_gain = 1.0;
+
+        while worker_running.load(Ordering::Acquire) {
+            if source_consumer.len() < chunk_len {
+                thread::sleep(WORKER_PARK);
+                continue;
+            }
+            source_consumer.pop_slice(&mut raw_chunk);
+
+            // `process_samples` mixes into `output` rather than overwriting
+            // it, so it has to start from silence each chunk.
+            output_chunk.iter_mut().for_each(|s| *s = (0.0, 0.0));
+
+            let new_sample_vector = *worker_position.lock().unwrap();
+            let distance_gain = motion.process_block(
+                new_sample_vector,
+                listener,
+                chunk_duration,
+                &raw_chunk,
+                &mut source_chunk,
+            );
+
+            let context = HrtfContext {
+                source: &source_chunk,
+                output: &mut output_chunk,
+                new_sample_vector,
+                prev_sample_vector,
+                prev_left_samples: &mut prev_left_samples,
+                prev_right_samples: &mut prev_right_samples,
+                prev_distance_gain,
+                new_distance_gain: distance_gain,
+            };
+            processor.process_samples(context);
+
+            prev_sample_vector = new_sample_vector;
+            prev_distance_gain = distance_gain;
+
+            for &(left, right) in &output_chunk {
+                // Reuse the same frame buffer rather than allocating one per
+                // frame: this loop runs on the thread that has to keep up
+                // with real-time block cadence.
+                output_frame[0] = left;
+                output_frame[1] = right;
+                output_producer.push_slice(&output_frame);
+            }
+        }
+    });
+
+    let input = HrtfStreamInput {
+        producer: source_producer,
+        position,
+    };
+    let output = HrtfStreamOutput {
+        consumer: output_consumer,
+        channels,
+        running,
+        worker: Some(worker),
+    };
+    (input, output)
+}
+
+/// The producer-side handle returned by [`hrtf_stream`].
+///
+/// Owns only the fields a capture/generator callback needs, so it can live on
+/// its own audio thread without contending with [`HrtfStreamOutput`].
+pub struct HrtfStreamInput {
+    producer: Producer<f32, Arc<HeapRb<f32>>>,
+    position: Arc<Mutex<Vec3>>,
+}
+
+impl HrtfStreamInput {
+    /// Push mono source samples onto the ring the worker reads from.
+    ///
+    /// Real-time safe: this only moves samples into a lock-free ring, it
+    /// never allocates or blocks.
+    pub fn push_source(&mut self, samples: &[f32]) {
+        self.producer.push_slice(samples);
+    }
+
+    /// Update the source's position relative to the listener.
+    ///
+    /// Picked up by the worker at the start of its next block. Locks a `Mutex`
+    /// that the worker also locks once per chunk to read the position.
+    pub fn set_position(&self, position: Vec3) {
+        *self.position.lock().unwrap() = position;
+    }
+}
+
+/// The consumer-side handle returned by [`hrtf_stream`].
+///
+/// Owns only the fields a playback callback needs, so it can live on its own
+/// audio thread without contending with [`HrtfStreamInput`]. Stops and joins
+/// the worker thread when dropped.
+pub struct HrtfStreamOutput {
+    consumer: Consumer<f32, Arc<HeapRb<f32>>>,
+    channels: usize,
+    running: Arc<AtomicBool>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl HrtfStreamOutput {
+    /// Drain as many rendered interleaved frames as are available into `out`,
+    /// zero-filling the remainder on underrun.
+    ///
+    /// Meant to be called from the playback callback: it never blocks and
+    /// never allocates.
+    pub fn fill_output(&mut self, out: &mut [f32]) {
+        debug_assert_eq!(out.len() % self.channels, 0);
+        let filled = self.consumer.pop_slice(out);
+        for sample in &mut out[filled..] {
+            *sample = 0.0;
+        }
+    }
+}
+
+impl Drop for HrtfStreamOutput {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Release);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    // Regression test for an output-accumulation bug: the worker reused its
+    // `output_chunk` buffer across iterations without clearing it first, and
+    // since `process_samples` mixes into `output` rather than overwriting it,
+    // every chunk's result got summed on top of all prior chunks', making the
+    // stream's amplitude ramp up without bound. A constant source at a fixed
+    // position should produce identical-magnitude chunks throughout.
+    #[test]
+    fn worker_does_not_accumulate_across_chunks() {
+        let interpolation_steps = 2;
+        let block_len = 4;
+        let channels = 2;
+        let sample_rate = 44_100.0;
+        let chunk_len = interpolation_steps * block_len;
+        let sphere = crate::test_fixtures::synthetic_hrir_sphere(sample_rate as u32);
+
+        let (mut input, mut output) = hrtf_stream(
+            sphere,
+            interpolation_steps,
+            block_len,
+            channels,
+            sample_rate,
+            Rolloff::None,
+            343.0,
+        );
+
+        let num_chunks = 3;
+        let frame_len = chunk_len * channels;
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        // Feed and drain one chunk at a time so the output ring (sized for a
+        // couple of chunks of jitter headroom, not `num_chunks` of them)
+        // never has to hold more than it's built for.
+        let mut chunk_magnitudes = Vec::with_capacity(num_chunks);
+        for _ in 0..num_chunks {
+            input.push_source(&vec![1.0f32; chunk_len]);
+
+            while output.consumer.len() < frame_len && Instant::now() < deadline {
+                thread::sleep(WORKER_PARK);
+            }
+            assert!(
+                output.consumer.len() >= frame_len,
+                "worker did not produce a chunk's worth of output in time"
+            );
+
+            let mut buf = vec![0.0f32; frame_len];
+            output.fill_output(&mut buf);
+            chunk_magnitudes.push(buf.iter().fold(0.0f32, |max, &s| max.max(s.abs())));
+        }
+        let first = chunk_magnitudes[0];
+        assert!(first > 0.0, "expected non-silent output, got {:?}", chunk_magnitudes);
+        for (i, &magnitude) in chunk_magnitudes.iter().enumerate() {
+            assert!(
+                (magnitude - first).abs() < first * 0.01,
+                "chunk {} magnitude {} diverged from chunk 0's {} — output is accumulating across chunks",
+                i,
+                magnitude,
+                first
+            );
+        }
+    }
+}
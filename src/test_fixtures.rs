@@ -0,0 +1,51 @@
+//! A minimal synthetic HRIR sphere for tests that need to drive a real
+//! `HrtfProcessor`, without depending on an actual `.bin` HRIR data file.
+//!
+//! Builds the smallest convex hull that can enclose the listener at the
+//! origin (a tetrahedron) with a trivial unit impulse response at every
+//! vertex, so convolution behaves like a scaled passthrough and test
+//! assertions can reason about exact output magnitudes.
+
+use crate::HrirSphere;
+use std::io::Cursor;
+
+pub fn synthetic_hrir_sphere(sample_rate: u32) -> HrirSphere {
+    let vertices: [(f32, f32, f32); 4] = [
+        (1.0, 1.0, 1.0),
+        (1.0, -1.0, -1.0),
+        (-1.0, 1.0, -1.0),
+        (-1.0, -1.0, 1.0),
+    ];
+    let faces: [[u32; 3]; 4] = [[0, 1, 2], [0, 1, 3], [0, 2, 3], [1, 2, 3]];
+    // A unit impulse at tap 0: convolution with it passes the signal through
+    // undelayed, so every point on the sphere has an identical,
+    // easy-to-predict response.
+    let length: u32 = 2;
+    let hrir = [1.0f32, 0.0];
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(b"HRIR");
+    bytes.extend_from_slice(&sample_rate.to_le_bytes());
+    bytes.extend_from_slice(&length.to_le_bytes());
+    bytes.extend_from_slice(&(vertices.len() as u32).to_le_bytes());
+    bytes.extend_from_slice(&((faces.len() * 3) as u32).to_le_bytes());
+    for face in &faces {
+        for &index in face {
+            bytes.extend_from_slice(&index.to_le_bytes());
+        }
+    }
+    for &(x, y, z) in &vertices {
+        bytes.extend_from_slice(&x.to_le_bytes());
+        bytes.extend_from_slice(&y.to_le_bytes());
+        bytes.extend_from_slice(&z.to_le_bytes());
+        for &sample in &hrir {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+        for &sample in &hrir {
+            bytes.extend_from_slice(&sample.to_le_bytes());
+        }
+    }
+
+    HrirSphere::new(Cursor::new(bytes), sample_rate)
+        .expect("synthetic HRIR sphere bytes should be well-formed")
+}